@@ -0,0 +1,153 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+use walkdir::WalkDir;
+
+use crate::plan_parser::{self, ChangeBreakdown};
+
+/// Selects which IaC backend drives discovery, planning, and change parsing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum BackendKind {
+    Terragrunt,
+    Terraform,
+    Tofu,
+}
+
+/// A pluggable IaC tool: finds the stacks to plan, builds the commands that
+/// plan and show them, and turns the resulting output into a [`ChangeBreakdown`].
+/// Implementations isolate one tool's quirks (binary name, discovery rule,
+/// JSON shape) behind this interface so the scheduler stays tool-agnostic.
+pub trait Backend: Send + Sync {
+    fn discover_dirs(&self, root: &str) -> Vec<PathBuf>;
+    fn plan_command(&self, dir: &Path, plan_file: &Path) -> Command;
+    fn show_command(&self, dir: &Path, plan_file: &Path) -> Command;
+
+    /// Parses the `show -json` output into a breakdown, falling back to
+    /// scraping `plan_stdout`'s `Plan:` line if the JSON is missing or invalid.
+    fn parse_changes(&self, show_output: &str, plan_stdout: &str) -> ChangeBreakdown {
+        plan_parser::parse_plan_changes_json(show_output)
+            .unwrap_or_else(|| plan_parser::parse_plan_changes_text(plan_stdout))
+    }
+}
+
+pub fn backend_for(kind: BackendKind) -> Box<dyn Backend> {
+    match kind {
+        BackendKind::Terragrunt => Box::new(Terragrunt),
+        BackendKind::Terraform => Box::new(Terraform),
+        BackendKind::Tofu => Box::new(OpenTofu),
+    }
+}
+
+/// Walks `root`, collecting the deduplicated parent directories of every
+/// file whose name satisfies `is_marker`.
+fn discover_dirs_by_marker(root: &str, is_marker: impl Fn(&str) -> bool) -> Vec<PathBuf> {
+    let mut seen = HashSet::new();
+    let mut dirs = Vec::new();
+
+    for entry in WalkDir::new(root).into_iter().filter_map(|e| e.ok()) {
+        let f_name = entry.file_name().to_string_lossy();
+        if is_marker(&f_name) {
+            if let Some(parent) = entry.path().parent() {
+                if seen.insert(parent.to_path_buf()) {
+                    dirs.push(parent.to_path_buf());
+                }
+            }
+        }
+    }
+
+    dirs
+}
+
+pub struct Terragrunt;
+
+impl Backend for Terragrunt {
+    fn discover_dirs(&self, root: &str) -> Vec<PathBuf> {
+        let dirs = discover_dirs_by_marker(root, |name| name.ends_with(".hcl"));
+        println!("Found {} terragrunt dirs", dirs.len());
+        dirs
+    }
+
+    fn plan_command(&self, dir: &Path, plan_file: &Path) -> Command {
+        let mut cmd = Command::new("terragrunt");
+        cmd.arg("plan").arg("-out").arg(plan_file).current_dir(dir);
+        cmd
+    }
+
+    fn show_command(&self, dir: &Path, plan_file: &Path) -> Command {
+        let mut cmd = Command::new("terragrunt");
+        cmd.arg("show").arg("-json").arg(plan_file).current_dir(dir);
+        cmd
+    }
+}
+
+/// Plain Terraform: a stack is any directory containing `.tf` files and a
+/// backend configuration (either a `backend` block in a `.tf` file or a
+/// generated `.terraform/` directory from a prior `init`). This excludes
+/// child module directories (e.g. `modules/foo/vars.tf`), which have `.tf`
+/// files but no backend of their own and aren't directly plannable.
+pub struct Terraform;
+
+/// Whether `dir` looks like a root Terraform stack rather than a module:
+/// either it's already been `init`ed, or one of its `.tf` files declares a
+/// `backend` block.
+fn has_backend_config(dir: &Path) -> bool {
+    if dir.join(".terraform").is_dir() {
+        return true;
+    }
+
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return false;
+    };
+
+    entries
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().extension().is_some_and(|ext| ext == "tf"))
+        .filter_map(|e| std::fs::read_to_string(e.path()).ok())
+        .any(|contents| contents.contains("backend \""))
+}
+
+impl Backend for Terraform {
+    fn discover_dirs(&self, root: &str) -> Vec<PathBuf> {
+        let dirs: Vec<PathBuf> = discover_dirs_by_marker(root, |name| name.ends_with(".tf"))
+            .into_iter()
+            .filter(|dir| has_backend_config(dir))
+            .collect();
+        println!("Found {} terraform dirs", dirs.len());
+        dirs
+    }
+
+    fn plan_command(&self, dir: &Path, plan_file: &Path) -> Command {
+        let mut cmd = Command::new("terraform");
+        cmd.arg("plan").arg("-out").arg(plan_file).current_dir(dir);
+        cmd
+    }
+
+    fn show_command(&self, dir: &Path, plan_file: &Path) -> Command {
+        let mut cmd = Command::new("terraform");
+        cmd.arg("show").arg("-json").arg(plan_file).current_dir(dir);
+        cmd
+    }
+}
+
+/// OpenTofu speaks the same CLI shape as Terraform, just via the `tofu` binary.
+pub struct OpenTofu;
+
+impl Backend for OpenTofu {
+    fn discover_dirs(&self, root: &str) -> Vec<PathBuf> {
+        let dirs = discover_dirs_by_marker(root, |name| name.ends_with(".tf"));
+        println!("Found {} tofu dirs", dirs.len());
+        dirs
+    }
+
+    fn plan_command(&self, dir: &Path, plan_file: &Path) -> Command {
+        let mut cmd = Command::new("tofu");
+        cmd.arg("plan").arg("-out").arg(plan_file).current_dir(dir);
+        cmd
+    }
+
+    fn show_command(&self, dir: &Path, plan_file: &Path) -> Command {
+        let mut cmd = Command::new("tofu");
+        cmd.arg("show").arg("-json").arg(plan_file).current_dir(dir);
+        cmd
+    }
+}
@@ -0,0 +1,140 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use regex::{Regex, RegexSet};
+use serde::Deserialize;
+
+/// Per-directory tweaks for stacks that need something other than the
+/// default plan invocation.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct DirOverride {
+    #[serde(default)]
+    pub extra_plan_args: Vec<String>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    #[serde(default)]
+    pub skip: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct RawConfig {
+    #[serde(default)]
+    included: Vec<String>,
+    #[serde(default)]
+    excluded: Vec<String>,
+    #[serde(default)]
+    overrides: HashMap<String, DirOverride>,
+}
+
+/// Parsed `drift.toml`: include/exclude patterns plus per-directory
+/// overrides, compiled once and reused across discovery and scheduling.
+///
+/// `included`/`excluded` are regular expressions, not shell globs — e.g.
+/// match any suffix with `.*`, not `*`.
+pub struct Config {
+    included: Option<RegexSet>,
+    excluded: Option<RegexSet>,
+    overrides: HashMap<String, DirOverride>,
+}
+
+#[derive(Debug)]
+pub struct ConfigError(String);
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl Config {
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let body = std::fs::read_to_string(path)
+            .map_err(|e| ConfigError(format!("reading {}: {}", path.display(), e)))?;
+        let raw: RawConfig = toml::from_str(&body)
+            .map_err(|e| ConfigError(format!("parsing {}: {}", path.display(), e)))?;
+
+        Ok(Self {
+            included: compile_set("included", &raw.included)?,
+            excluded: compile_set("excluded", &raw.excluded)?,
+            overrides: raw.overrides,
+        })
+    }
+
+    /// Applies the included/excluded patterns and any per-directory `skip`
+    /// override, keeping only the directories that are in scope. Warns on
+    /// stderr about any `overrides` entry that matched no discovered
+    /// directory, since a typo'd key (relative vs. absolute, trailing
+    /// slash) otherwise silently no-ops.
+    pub fn filter_dirs(&self, dirs: Vec<PathBuf>) -> Vec<PathBuf> {
+        self.warn_on_unmatched_overrides(&dirs);
+        dirs.into_iter().filter(|dir| self.is_in_scope(dir)).collect()
+    }
+
+    pub fn override_for(&self, dir: &Path) -> Option<&DirOverride> {
+        self.overrides.get(&normalize_dir(dir))
+    }
+
+    fn warn_on_unmatched_overrides(&self, dirs: &[PathBuf]) {
+        for key in self.overrides.keys() {
+            let matched = dirs.iter().any(|dir| &normalize_dir(dir) == key);
+            if !matched {
+                eprintln!(
+                    "warning: drift.toml override {:?} matched no discovered directory",
+                    key
+                );
+            }
+        }
+    }
+
+    fn is_in_scope(&self, dir: &Path) -> bool {
+        if self.override_for(dir).is_some_and(|o| o.skip) {
+            return false;
+        }
+
+        let dir_str = dir.to_string_lossy();
+
+        if let Some(excluded) = &self.excluded {
+            if excluded.is_match(&dir_str) {
+                return false;
+            }
+        }
+
+        match &self.included {
+            Some(included) => included.is_match(&dir_str),
+            None => true,
+        }
+    }
+}
+
+/// Normalizes a discovered directory to a single canonical string, stripping
+/// a trailing slash so the directories `"foo/bar"` and `"foo/bar/"` both
+/// match the override key `"foo/bar"`. Override keys are compared as written.
+fn normalize_dir(dir: &Path) -> String {
+    dir.to_string_lossy().trim_end_matches('/').to_string()
+}
+
+/// Compiles `patterns` (from the `field` array in `drift.toml`) into a
+/// [`RegexSet`]. Patterns are regular expressions, not shell globs; a
+/// common mistake like `"**/modules/**"` fails here with a clear, specific
+/// error instead of a generic one from the whole set.
+fn compile_set(field: &str, patterns: &[String]) -> Result<Option<RegexSet>, ConfigError> {
+    if patterns.is_empty() {
+        return Ok(None);
+    }
+
+    for pattern in patterns {
+        if let Err(e) = Regex::new(pattern) {
+            return Err(ConfigError(format!(
+                "invalid regex in `{}`: {:?}: {} (patterns are regular expressions, not glob patterns)",
+                field, pattern, e
+            )));
+        }
+    }
+
+    RegexSet::new(patterns)
+        .map(Some)
+        .map_err(|e| ConfigError(format!("compiling `{}` pattern set: {}", field, e)))
+}
@@ -0,0 +1,118 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+
+use tokio::process::Command;
+use tokio::time::sleep;
+
+/// Stderr substrings that indicate a transient failure worth retrying:
+/// state-lock contention, a cloud API throttling response, or a reset connection.
+const RETRYABLE_MARKERS: &[&str] = &[
+    "Error acquiring the state lock",
+    "ConditionalCheckFailedException",
+    "rate limit",
+    "RequestLimitExceeded",
+    "Throttling",
+    "connection reset",
+];
+
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub retries: u32,
+    pub backoff: Duration,
+}
+
+impl RetryPolicy {
+    pub fn none() -> Self {
+        Self {
+            retries: 0,
+            backoff: Duration::ZERO,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CommandOutput {
+    pub stdout: String,
+    pub stderr: String,
+    pub success: bool,
+    pub exit_code: Option<i32>,
+}
+
+/// A command that couldn't be run at all (e.g. the binary is missing),
+/// carrying enough context to explain exactly what was attempted and where.
+#[derive(Debug)]
+pub struct CommandError {
+    pub argv: Vec<String>,
+    pub dir: PathBuf,
+    pub source: std::io::Error,
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "failed to execute `{}` in {}: {}",
+            self.argv.join(" "),
+            self.dir.display(),
+            self.source
+        )
+    }
+}
+
+impl std::error::Error for CommandError {}
+
+fn argv_of(command: &Command) -> Vec<String> {
+    let std_command = command.as_std();
+    std::iter::once(std_command.get_program())
+        .chain(std_command.get_args())
+        .map(|s| s.to_string_lossy().to_string())
+        .collect()
+}
+
+fn is_retryable(stderr: &str) -> bool {
+    RETRYABLE_MARKERS.iter().any(|marker| stderr.contains(marker))
+}
+
+/// Runs `command`, retrying up to `policy.retries` times (with a backoff
+/// that grows linearly with the attempt count) when the failure looks
+/// transient. Returns the raw output even on a non-zero exit; only a
+/// failure to launch the process at all surfaces as a [`CommandError`].
+pub async fn run(
+    dir: &Path,
+    mut command: Command,
+    policy: &RetryPolicy,
+) -> Result<CommandOutput, CommandError> {
+    let argv = argv_of(&command);
+    let mut attempt = 0;
+
+    loop {
+        let output = command
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .output()
+            .await
+            .map_err(|e| CommandError {
+                argv: argv.clone(),
+                dir: dir.to_path_buf(),
+                source: e,
+            })?;
+
+        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        let success = output.status.success();
+
+        if success || attempt >= policy.retries || !is_retryable(&stderr) {
+            return Ok(CommandOutput {
+                stdout,
+                stderr,
+                success,
+                exit_code: output.status.code(),
+            });
+        }
+
+        attempt += 1;
+        sleep(policy.backoff * attempt).await;
+    }
+}
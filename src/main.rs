@@ -1,97 +1,138 @@
 use clap::Parser;
-use std::{path::PathBuf, process::Stdio};
-use tokio::{process::Command, task::JoinSet};
-// use tokio::process::Command;
-use walkdir::WalkDir;
+use serde::Serialize;
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::{sync::Semaphore, task::JoinSet};
+
+mod backend;
+mod config;
+mod exec;
+mod plan_parser;
+mod report;
+
+use backend::{Backend, BackendKind};
+use config::{Config, DirOverride};
+use exec::RetryPolicy;
+use plan_parser::ChangeBreakdown;
+use report::OutputFormat;
 
 #[derive(Debug, Parser)]
 #[command(author, version, about)]
 struct Args {
+    /// Root directory to walk for stacks. Required unless `--stdin` (or `--path -`) is used.
     #[arg(short, long)]
-    path: String,
-    #[arg(short, long)]
+    path: Option<String>,
+    /// Maximum number of plans to run at once. Must be at least 1.
+    #[arg(short, long, value_parser = parse_max_concurency)]
     max_concurency: usize,
+    /// Also write the full drift report to this path; the summary lines still go to stdout.
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    /// Format of the report written to `--output`.
+    #[arg(short, long, value_enum, default_value = "text")]
+    format: OutputFormat,
+    /// Which IaC tool to drive.
+    #[arg(short, long, value_enum, default_value = "terragrunt")]
+    backend: BackendKind,
+    /// Path to a `drift.toml` with include/exclude filters and per-directory overrides.
+    #[arg(short, long)]
+    config: Option<PathBuf>,
+    /// Read newline-delimited directory paths from stdin instead of walking `--path`.
+    /// Implied when `--path -` is given. One of `--stdin` or `--path` is required.
+    #[arg(long)]
+    stdin: bool,
+    /// Retries for a plan that fails on a transient error (state lock, API throttling).
+    #[arg(long, default_value_t = 0)]
+    retries: u32,
+    /// Base backoff between retries, in milliseconds; grows linearly with the attempt count.
+    #[arg(long, default_value_t = 1000)]
+    retry_backoff: u64,
 }
 
-#[derive(Debug, Clone)]
+/// Rejects `--max-concurency 0`, which would construct a zero-permit
+/// semaphore and hang every scheduled plan forever.
+fn parse_max_concurency(s: &str) -> Result<usize, String> {
+    let value: usize = s.parse().map_err(|e| format!("{e}"))?;
+    if value == 0 {
+        return Err("must be at least 1".to_string());
+    }
+    Ok(value)
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub enum PlanStatus {
     Success,
     Failed,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 struct PlanResult {
     pub path: String,
     pub status: PlanStatus,
     pub changes_count: u32,
+    pub changes: ChangeBreakdown,
     pub stdout: String,
     pub stderr: String,
     pub plan_file: String,
     pub error: Option<String>,
 }
 
-pub fn find_tg_dirs(path: &str) -> Vec<PathBuf> {
-    let mut tg_dirs = Vec::new();
-
-    for entry in WalkDir::new(path).into_iter().filter_map(|e| e.ok()) {
-        let f_name = entry.file_name().to_string_lossy();
-        if f_name.ends_with(".hcl") {
-            if let Some(parent) = entry.path().parent() {
-                tg_dirs.push(parent.to_path_buf());
-            }
-        }
-    }
-    println!("Found {} terragrunt dirs", tg_dirs.len());
-    tg_dirs
-}
-
-async fn run_terragrunt_plan(path: PathBuf) -> PlanResult {
+async fn run_plan(
+    backend: &dyn Backend,
+    path: PathBuf,
+    dir_override: Option<&DirOverride>,
+    retry_policy: &RetryPolicy,
+) -> PlanResult {
     let plan_file = path.join("plan.tfplan");
     let path_string = path.to_string_lossy().to_string();
 
-    let output = Command::new("terragrunt")
-        .arg("plan")
-        .arg("-out")
-        .arg(&plan_file)
-        .current_dir(&path)
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .output()
-        .await;
-
-    match output {
-        Ok(output) => {
-            let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-            let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-            if output.status.success() {
-                let changes_count = parse_plan_changes(&stdout);
-
-                if changes_count > 0 {
-                    println!("Drift in {}: {} changes", path_string, changes_count);
-                } else {
-                    println!("No drift in {}", path_string);
-                }
+    let mut command = backend.plan_command(&path, &plan_file);
+    if let Some(dir_override) = dir_override {
+        command.args(&dir_override.extra_plan_args);
+        command.envs(&dir_override.env);
+    }
 
-                PlanResult {
-                    path: path_string,
-                    status: PlanStatus::Success,
-                    changes_count,
-                    stdout,
-                    stderr,
-                    plan_file: plan_file.to_string_lossy().to_string(),
-                    error: None,
-                }
+    match exec::run(&path, command, retry_policy).await {
+        Ok(output) if output.success => {
+            let changes = resolve_change_breakdown(backend, &path, &plan_file, &output.stdout, retry_policy).await;
+            let changes_count = changes.total();
+
+            if changes_count > 0 {
+                println!("Drift in {}: {} changes", path_string, changes_count);
             } else {
-                println!("Plan failed for {}", path_string);
-                PlanResult {
-                    path: path_string,
-                    status: PlanStatus::Failed,
-                    changes_count: 0,
-                    stdout,
-                    stderr: stderr.clone(),
-                    plan_file: String::new(),
-                    error: Some(stderr),
-                }
+                println!("No drift in {}", path_string);
+            }
+
+            PlanResult {
+                path: path_string,
+                status: PlanStatus::Success,
+                changes_count,
+                changes,
+                stdout: output.stdout,
+                stderr: output.stderr,
+                plan_file: plan_file.to_string_lossy().to_string(),
+                error: None,
+            }
+        }
+        Ok(output) => {
+            println!("Plan failed for {}", path_string);
+            let error = Some(format!(
+                "exit {}: {}",
+                output.exit_code.map_or_else(|| "signal".to_string(), |c| c.to_string()),
+                output.stderr
+            ));
+            PlanResult {
+                path: path_string,
+                status: PlanStatus::Failed,
+                changes_count: 0,
+                changes: ChangeBreakdown::default(),
+                stdout: output.stdout,
+                stderr: output.stderr.clone(),
+                plan_file: String::new(),
+                error,
             }
         }
         Err(e) => {
@@ -100,49 +141,58 @@ async fn run_terragrunt_plan(path: PathBuf) -> PlanResult {
                 path: path_string,
                 status: PlanStatus::Failed,
                 changes_count: 0,
+                changes: ChangeBreakdown::default(),
                 stdout: String::new(),
                 stderr: String::new(),
                 plan_file: String::new(),
-                error: Some(format!("Failed to execute: {}", e)),
+                error: Some(e.to_string()),
             }
         }
     }
 }
 
-fn parse_plan_changes(output: &str) -> u32 {
-    if output.contains("No changes") {
-        return 0;
-    }
-
-    for line in output.lines() {
-        if line.contains("Plan:") {
-            let numbers: Vec<u32> = line
-                .split_whitespace()
-                .filter_map(|s| s.parse().ok())
-                .collect();
-            return numbers.iter().sum();
-        }
-    }
+/// Prefers the backend's JSON `show` output for a precise
+/// create/update/delete/replace breakdown, falling back to scraping the
+/// `Plan:` line when the JSON show fails or can't be parsed.
+async fn resolve_change_breakdown(
+    backend: &dyn Backend,
+    path: &Path,
+    plan_file: &Path,
+    plan_stdout: &str,
+    retry_policy: &RetryPolicy,
+) -> ChangeBreakdown {
+    let show_command = backend.show_command(path, plan_file);
+    let show_stdout = match exec::run(path, show_command, retry_policy).await {
+        Ok(output) if output.success => output.stdout,
+        _ => String::new(),
+    };
 
-    0
+    backend.parse_changes(&show_stdout, plan_stdout)
 }
 
-pub async fn run_plans(dirs: Vec<PathBuf>, max_concurency: usize) -> Vec<PlanResult> {
+pub async fn run_plans(
+    backend: Arc<dyn Backend>,
+    dirs: Vec<PathBuf>,
+    max_concurency: usize,
+    config: Option<Arc<Config>>,
+    retry_policy: RetryPolicy,
+) -> Vec<PlanResult> {
+    let semaphore = Arc::new(Semaphore::new(max_concurency));
     let mut tasks = JoinSet::new();
     let mut results = Vec::new();
-    let mut count = 0;
 
     for dir in dirs {
-        tasks.spawn(run_terragrunt_plan(dir));
-        count += 1;
-
-        if count >= max_concurency {
-            if let Some(result) = tasks.join_next().await {
-                if let Ok(plan_result) = result {
-                    results.push(plan_result);
-                }
-            }
-        }
+        let backend = Arc::clone(&backend);
+        let semaphore = Arc::clone(&semaphore);
+        let config = config.clone();
+        tasks.spawn(async move {
+            let _permit = semaphore
+                .acquire_owned()
+                .await
+                .expect("semaphore is never closed");
+            let dir_override = config.as_deref().and_then(|c| c.override_for(&dir)).cloned();
+            run_plan(backend.as_ref(), dir, dir_override.as_ref(), &retry_policy).await
+        });
     }
 
     while let Some(result) = tasks.join_next().await {
@@ -158,10 +208,44 @@ pub async fn run_plans(dirs: Vec<PathBuf>, max_concurency: usize) -> Vec<PlanRes
 async fn main() {
     tracing_subscriber::fmt::init();
     let args = Args::parse();
-    let tg_dirs = find_tg_dirs(&args.path);
+    let backend: Arc<dyn Backend> = Arc::from(backend::backend_for(args.backend));
+
+    let config = match &args.config {
+        Some(path) => match Config::load(path) {
+            Ok(config) => Some(Arc::new(config)),
+            Err(e) => {
+                eprintln!("Failed to load config {}: {}", path.display(), e);
+                std::process::exit(1);
+            }
+        },
+        None => None,
+    };
+
+    let mut dirs = if args.stdin || args.path.as_deref() == Some("-") {
+        read_dirs_from_stdin()
+    } else {
+        match &args.path {
+            Some(path) => backend.discover_dirs(path),
+            None => {
+                eprintln!("--path is required unless --stdin (or --path -) is used");
+                std::process::exit(1);
+            }
+        }
+    };
+    if let Some(config) = &config {
+        dirs = config.filter_dirs(dirs);
+    }
     let max_concurency = args.max_concurency;
-    let results = run_plans(tg_dirs, max_concurency).await;
-    for result in results {
+    let retry_policy = RetryPolicy {
+        retries: args.retries,
+        backoff: Duration::from_millis(args.retry_backoff),
+    };
+
+    let started = Instant::now();
+    let results = run_plans(backend, dirs, max_concurency, config, retry_policy).await;
+    let elapsed = started.elapsed();
+
+    for result in &results {
         match result.status {
             PlanStatus::Success => {
                 if result.changes_count > 0 {
@@ -173,4 +257,52 @@ async fn main() {
             }
         }
     }
+
+    if let Some(output) = &args.output {
+        match args.format {
+            OutputFormat::Json => {
+                if let Err(e) = report::write_json_report(output, &results, elapsed) {
+                    eprintln!("Failed to write report to {}: {}", output.display(), e);
+                }
+            }
+            OutputFormat::Text => {
+                if let Err(e) = report::write_atomic(output, &render_text_report(&results)) {
+                    eprintln!("Failed to write report to {}: {}", output.display(), e);
+                }
+            }
+        }
+    }
+}
+
+/// Reads newline-delimited directory paths from stdin, trimming whitespace
+/// and skipping blank lines so it composes cleanly with `git diff
+/// --name-only`, `find`, or a changed-modules detector.
+fn read_dirs_from_stdin() -> Vec<PathBuf> {
+    std::io::stdin()
+        .lines()
+        .map_while(Result::ok)
+        .map(|line| line.trim().to_string())
+        .filter(|line| !line.is_empty())
+        .map(PathBuf::from)
+        .collect()
+}
+
+fn render_text_report(results: &[PlanResult]) -> String {
+    let mut out = String::new();
+    for result in results {
+        match result.status {
+            PlanStatus::Success => {
+                if result.changes_count > 0 {
+                    out.push_str(&format!(
+                        "Store drift: {} -> {}\n",
+                        result.path, result.changes_count
+                    ));
+                }
+            }
+            PlanStatus::Failed => {
+                out.push_str(&format!("Store error: {} -> {:?}\n", result.path, result.error));
+            }
+        }
+    }
+    out
 }
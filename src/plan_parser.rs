@@ -0,0 +1,159 @@
+use serde::{Deserialize, Serialize};
+
+/// Counts of resource changes bucketed by the action Terraform/Terragrunt
+/// would take, as reported by `terragrunt show -json`.
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct ChangeBreakdown {
+    pub create: u32,
+    pub update: u32,
+    pub delete: u32,
+    pub replace: u32,
+    /// Changes counted by the legacy "Plan:" line parser, which can't
+    /// tell create/update/delete/replace apart, plus any JSON resource
+    /// change whose action list is none of the recognized combinations.
+    pub unknown: u32,
+}
+
+impl ChangeBreakdown {
+    pub fn total(&self) -> u32 {
+        self.create + self.update + self.delete + self.replace + self.unknown
+    }
+
+    fn from_text_total(total: u32) -> Self {
+        Self {
+            unknown: total,
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ShowOutput {
+    #[serde(default)]
+    resource_changes: Vec<ResourceChange>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResourceChange {
+    change: Change,
+}
+
+#[derive(Debug, Deserialize)]
+struct Change {
+    actions: Vec<String>,
+}
+
+/// Parses the JSON emitted by `terragrunt show -json plan.tfplan`, bucketing
+/// every resource change by its action. Returns `None` if `output` isn't
+/// valid plan JSON, so the caller can fall back to [`parse_plan_changes_text`].
+pub fn parse_plan_changes_json(output: &str) -> Option<ChangeBreakdown> {
+    let parsed: ShowOutput = serde_json::from_str(output).ok()?;
+    let mut breakdown = ChangeBreakdown::default();
+
+    for resource_change in &parsed.resource_changes {
+        let actions = &resource_change.change.actions;
+        match actions.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+            ["no-op"] | [] => {}
+            ["create"] => breakdown.create += 1,
+            ["update"] => breakdown.update += 1,
+            ["delete"] => breakdown.delete += 1,
+            ["delete", "create"] | ["create", "delete"] => breakdown.replace += 1,
+            _ => breakdown.unknown += 1,
+        }
+    }
+
+    Some(breakdown)
+}
+
+/// Legacy fallback: sums every integer on the `Plan:` summary line. Kept for
+/// the case where `terragrunt show -json` isn't available (e.g. older
+/// Terragrunt/Terraform versions).
+pub fn parse_plan_changes_text(output: &str) -> ChangeBreakdown {
+    if output.contains("No changes") {
+        return ChangeBreakdown::default();
+    }
+
+    for line in output.lines() {
+        if line.contains("Plan:") {
+            let total: u32 = line
+                .split_whitespace()
+                .filter_map(|s| s.parse::<u32>().ok())
+                .sum();
+            return ChangeBreakdown::from_text_total(total);
+        }
+    }
+
+    ChangeBreakdown::default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn show_output(actions: &[&[&str]]) -> String {
+        let resource_changes: Vec<_> = actions
+            .iter()
+            .map(|a| serde_json::json!({"change": {"actions": a}}))
+            .collect();
+        serde_json::json!({ "resource_changes": resource_changes }).to_string()
+    }
+
+    #[test]
+    fn json_buckets_create_update_delete() {
+        let output = show_output(&[&["create"], &["update"], &["delete"]]);
+        let breakdown = parse_plan_changes_json(&output).unwrap();
+        assert_eq!(breakdown.create, 1);
+        assert_eq!(breakdown.update, 1);
+        assert_eq!(breakdown.delete, 1);
+        assert_eq!(breakdown.replace, 0);
+        assert_eq!(breakdown.total(), 3);
+    }
+
+    #[test]
+    fn json_buckets_replace_in_either_action_order() {
+        let output = show_output(&[&["delete", "create"], &["create", "delete"]]);
+        let breakdown = parse_plan_changes_json(&output).unwrap();
+        assert_eq!(breakdown.replace, 2);
+        assert_eq!(breakdown.total(), 2);
+    }
+
+    #[test]
+    fn json_ignores_no_op_and_empty_actions() {
+        let output = show_output(&[&["no-op"], &[]]);
+        let breakdown = parse_plan_changes_json(&output).unwrap();
+        assert_eq!(breakdown.total(), 0);
+    }
+
+    #[test]
+    fn json_buckets_unrecognized_action_combos_as_unknown() {
+        let output = show_output(&[&["read"]]);
+        let breakdown = parse_plan_changes_json(&output).unwrap();
+        assert_eq!(breakdown.unknown, 1);
+    }
+
+    #[test]
+    fn json_returns_none_for_invalid_json() {
+        assert!(parse_plan_changes_json("not json").is_none());
+    }
+
+    #[test]
+    fn text_reports_no_changes_as_empty() {
+        let breakdown = parse_plan_changes_text("No changes. Your infrastructure matches.");
+        assert_eq!(breakdown.total(), 0);
+    }
+
+    #[test]
+    fn text_sums_the_plan_summary_line_as_unknown() {
+        let breakdown = parse_plan_changes_text(
+            "Plan: 2 to add, 1 to change, 3 to destroy.",
+        );
+        assert_eq!(breakdown.unknown, 6);
+        assert_eq!(breakdown.create, 0);
+    }
+
+    #[test]
+    fn text_defaults_when_no_plan_line_present() {
+        let breakdown = parse_plan_changes_text("some unrelated output");
+        assert_eq!(breakdown.total(), 0);
+    }
+}
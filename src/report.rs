@@ -0,0 +1,66 @@
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::PlanResult;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    Text,
+    Json,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Summary {
+    pub total_dirs: usize,
+    pub with_drift: usize,
+    pub failed: usize,
+    pub wall_clock_ms: u128,
+}
+
+impl Summary {
+    pub fn from_results(results: &[PlanResult], elapsed: Duration) -> Self {
+        let with_drift = results
+            .iter()
+            .filter(|r| matches!(r.status, crate::PlanStatus::Success) && r.changes_count > 0)
+            .count();
+        let failed = results
+            .iter()
+            .filter(|r| matches!(r.status, crate::PlanStatus::Failed))
+            .count();
+
+        Self {
+            total_dirs: results.len(),
+            with_drift,
+            failed,
+            wall_clock_ms: elapsed.as_millis(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Report<'a> {
+    pub summary: Summary,
+    pub results: &'a [PlanResult],
+}
+
+/// Writes `body` to `path` via write-to-temp-then-rename so readers never
+/// observe a partial file, regardless of report format.
+pub fn write_atomic(path: &Path, body: &str) -> io::Result<()> {
+    let tmp_path = path.with_extension("tmp");
+    std::fs::write(&tmp_path, body)?;
+    std::fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+/// Serializes `results` as JSON and writes them to `path` atomically.
+pub fn write_json_report(path: &Path, results: &[PlanResult], elapsed: Duration) -> io::Result<()> {
+    let report = Report {
+        summary: Summary::from_results(results, elapsed),
+        results,
+    };
+    let body = serde_json::to_string_pretty(&report)?;
+    write_atomic(path, &body)
+}